@@ -3,9 +3,17 @@
     Instance of `CanvasDraw` are done using `canvas.begin_draw()`.
 */
 use winapi::shared::winerror::S_OK;
+use winapi::um::d2d1::{
+    D2D1_POINT_2F, D2D1_ELLIPSE, D2D1_ROUNDED_RECT, D2D1_SIZE_F, D2D1_ARC_SEGMENT,
+    D2D1_BEZIER_SEGMENT, D2D1_QUADRATIC_BEZIER_SEGMENT, D2D1_FIGURE_BEGIN_FILLED, D2D1_FIGURE_BEGIN_HOLLOW,
+    D2D1_FIGURE_END_CLOSED, D2D1_FIGURE_END_OPEN, ID2D1Geometry, ID2D1PathGeometry, ID2D1GeometrySink,
+    D2D1_ANTIALIAS_MODE_PER_PRIMITIVE, D2D1_LAYER_PARAMETERS, D2D1_LAYER_OPTIONS_NONE
+};
+use winapi::um::dwrite::{IDWriteTextLayout, DWRITE_MEASURING_MODE_NATURAL, DWRITE_MEASURING_MODE_GDI_CLASSIC, DWRITE_MEASURING_MODE_GDI_NATURAL};
 use crate::win32::canvas;
 use super::{CanvasError, Rect, Color, Matrix3x2F, BaseBrush, StrokeStyle, DrawTextOptions, MeasuringMode, WriteTextFormat};
 use std::convert::TryInto;
+use std::ptr;
 
 
 pub struct CanvasDraw<'a> {
@@ -102,6 +110,145 @@ impl<'a> CanvasDraw<'a> {
         }
     }
 
+    /// Draws the outline of a rounded rectangle that has the specified dimensions, corner radius and stroke style.
+    pub fn draw_rounded_rectangle<B: TryInto<BaseBrush>>(&self, rect: &D2D1_ROUNDED_RECT, brush: B, stroke_width: f32, stroke_style: &StrokeStyle) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.DrawRoundedRectangle(rect, base.0, stroke_width, stroke_style.handle);
+        }
+    }
+
+    /// Uses a brush to fill the interior of a rounded rectangle.
+    /// Panics if the brush is not bound to the renderer
+    pub fn fill_rounded_rectangle<B: TryInto<BaseBrush>>(&self, rect: &D2D1_ROUNDED_RECT, brush: B) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.FillRoundedRectangle(rect, base.0);
+        }
+    }
+
+    /// Draws the outline of an ellipse that has the specified dimensions and stroke style.
+    pub fn draw_ellipse<B: TryInto<BaseBrush>>(&self, ellipse: &D2D1_ELLIPSE, brush: B, stroke_width: f32, stroke_style: &StrokeStyle) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.DrawEllipse(ellipse, base.0, stroke_width, stroke_style.handle);
+        }
+    }
+
+    /// Uses a brush to fill the interior of an ellipse.
+    /// Panics if the brush is not bound to the renderer
+    pub fn fill_ellipse<B: TryInto<BaseBrush>>(&self, ellipse: &D2D1_ELLIPSE, brush: B) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.FillEllipse(ellipse, base.0);
+        }
+    }
+
+    /// Draws a line segment between two points using the specified stroke style.
+    pub fn draw_line<B: TryInto<BaseBrush>>(&self, p0: (f32, f32), p1: (f32, f32), brush: B, stroke_width: f32, stroke_style: &StrokeStyle) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.DrawLine(
+                D2D1_POINT_2F { x: p0.0, y: p0.1 },
+                D2D1_POINT_2F { x: p1.0, y: p1.1 },
+                base.0,
+                stroke_width,
+                stroke_style.handle
+            );
+        }
+    }
+
+    /// Draws the outline of an arbitrary `Path` geometry using the specified stroke style.
+    pub fn draw_geometry<B: TryInto<BaseBrush>>(&self, path: &Path, brush: B, stroke_width: f32, stroke_style: &StrokeStyle) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.DrawGeometry(path.as_geometry(), base.0, stroke_width, stroke_style.handle);
+        }
+    }
+
+    /// Uses a brush to fill the interior of an arbitrary `Path` geometry.
+    /// Panics if the brush is not bound to the renderer
+    pub fn fill_geometry<B: TryInto<BaseBrush>>(&self, path: &Path, brush: B) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.FillGeometry(path.as_geometry(), base.0, ptr::null_mut());
+        }
+    }
+
+    /// Pushes an axis-aligned clip rectangle onto the render target's clip stack. Every draw call
+    /// is restricted to this rectangle until a matching `pop_clip` is called.
+    /// Prefer `ClipGuard::new` if the push/pop pairing must not be leaked across an early return.
+    pub fn push_clip(&self, rect: &Rect) {
+        unsafe {
+            let target = &*self.base.render_target;
+            target.PushAxisAlignedClip(rect, D2D1_ANTIALIAS_MODE_PER_PRIMITIVE);
+        }
+    }
+
+    /// Pops the clip rectangle pushed by the last matching call to `push_clip`.
+    pub fn pop_clip(&self) {
+        unsafe {
+            let target = &*self.base.render_target;
+            target.PopAxisAlignedClip();
+        }
+    }
+
+    /// Pushes a layer onto the render target, scoping subsequent draw calls to `params`'s content
+    /// bounds, geometric mask and opacity. Must be paired with a matching call to `pop_layer`.
+    /// Prefer `LayerGuard::new` if the push/pop pairing must not be leaked across an early return.
+    pub fn push_layer(&self, params: &LayerParameters) {
+        unsafe {
+            let target = &*self.base.render_target;
+
+            // Passing a null layer lets Direct2D manage the layer resource itself, so there's
+            // no `ID2D1Layer` to keep alive (or release) across the matching `pop_layer`.
+            target.PushLayer(&params.as_d2d1(), ptr::null_mut());
+        }
+    }
+
+    /// Pops the layer pushed by the last matching call to `push_layer`.
+    pub fn pop_layer(&self) {
+        unsafe {
+            let target = &*self.base.render_target;
+            target.PopLayer();
+        }
+    }
+
     /// Draws the specified text onto the canvas
     /// You might want to use `draw_simple_text` for a simplified interface over this function
     ///
@@ -112,12 +259,36 @@ impl<'a> CanvasDraw<'a> {
     ///  - brush: The brush used to paint the text.
     ///  - options: A value that indicates whether the text should be snapped to pixel boundaries and whether the text should be clipped to the layout rectangle. 
     ///  - measure: A value that indicates how glyph metrics are used to measure text when it is formatted.
-    pub fn draw_text<'b, B: TryInto<BaseBrush>>(&self, _text: &'b str, _fmt: WriteTextFormat, _area: &Rect, _brush: B, _options: DrawTextOptions, _measure: MeasuringMode) {
+    pub fn draw_text<'b, B: TryInto<BaseBrush>>(&self, text: &'b str, fmt: WriteTextFormat, area: &Rect, brush: B, options: DrawTextOptions, measure: MeasuringMode) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
 
+        let measure = match measure {
+            MeasuringMode::Natural => DWRITE_MEASURING_MODE_NATURAL,
+            MeasuringMode::GdiClassic => DWRITE_MEASURING_MODE_GDI_CLASSIC,
+            MeasuringMode::GdiNatural => DWRITE_MEASURING_MODE_GDI_NATURAL,
+        };
+
+        let wide: Vec<u16> = text.encode_utf16().collect();
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.DrawText(
+                wide.as_ptr(),
+                wide.len() as u32,
+                fmt.handle,
+                area,
+                base.0,
+                options.bits(),
+                measure
+            );
+        }
     }
 
     /// Draws the specified text onto the canvas
-    /// Even though it might not look like it, this is a simplified interface over `draw_text` 
+    /// Even though it might not look like it, this is a simplified interface over `draw_text`
     ///
     // Arguments:
     ///  - text: The string of text to draw
@@ -125,13 +296,14 @@ impl<'a> CanvasDraw<'a> {
     ///  - pos: The position of the text
     ///  - brush: The brush used to paint the text.
     pub fn draw_simple_text<'b, B: TryInto<BaseBrush>>(&self, text: &'a str, fmt: WriteTextFormat, pos: (f32, f32), brush: B) {
+        let (width, height) = self.size();
         let area = Rect {
             left: pos.0,
             top: pos.1,
-            right: 1.0,
-            bottom: 1.0,
+            right: width,
+            bottom: height,
         };
-        
+
         self.draw_text(
             text,
             fmt,
@@ -142,4 +314,282 @@ impl<'a> CanvasDraw<'a> {
         )
     }
 
+    /// Draws a precomputed text layout onto the canvas.
+    /// Use this over `draw_text` when the layout has already been measured/aligned and needs to be redrawn across several frames.
+    ///
+    /// Arguments:
+    ///  - layout: The text layout to draw
+    ///  - pos: The position of the top-left corner of the layout box
+    ///  - brush: The brush used to paint the text.
+    ///  - options: A value that indicates whether the text should be snapped to pixel boundaries and whether the text should be clipped to the layout rectangle.
+    pub fn draw_text_layout<B: TryInto<BaseBrush>>(&self, layout: &IDWriteTextLayout, pos: (f32, f32), brush: B, options: DrawTextOptions) {
+        let base = match brush.try_into() {
+            Ok(b) => b,
+            Err(_) => panic!("Brush is invalid")
+        };
+
+        let origin = D2D1_POINT_2F { x: pos.0, y: pos.1 };
+
+        unsafe {
+            let target = &*self.base.render_target;
+            target.DrawTextLayout(origin, layout as *const _ as *mut _, base.0, options.bits());
+        }
+    }
+
+}
+
+/// Direction in which an elliptical arc is swept, mirroring `D2D1_SWEEP_DIRECTION`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SweepDirection {
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Whether an elliptical arc is larger than 180 degrees, mirroring `D2D1_ARC_SIZE`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArcSize {
+    Small,
+    Large,
+}
+
+/**
+A builder for arbitrary vector paths, backed by a `ID2D1PathGeometry`/`ID2D1GeometrySink`.
+Mirrors a BezPath-style API: start a figure with `begin_figure`, add segments with `line_to`,
+`quadratic_bezier_to`, `cubic_bezier_to`, or `arc_to`, then call `close` to finish the path.
+
+The finished path can be drawn on a canvas using `CanvasDraw::draw_geometry` / `CanvasDraw::fill_geometry`.
+*/
+pub struct Path {
+    geometry: *mut ID2D1PathGeometry,
+    sink: *mut ID2D1GeometrySink,
+}
+
+impl Path {
+
+    /// Creates a new, empty path bound to the same Direct2D factory as the canvas `renderer`.
+    pub fn new(renderer: &canvas::CanvasRenderer) -> Result<Path, CanvasError> {
+        unsafe {
+            let factory = &*renderer.factory;
+
+            let mut geometry: *mut ID2D1PathGeometry = ptr::null_mut();
+            match factory.CreatePathGeometry(&mut geometry) {
+                S_OK => {},
+                e => { return Err(CanvasError::Other(e)); }
+            }
+
+            let mut sink: *mut ID2D1GeometrySink = ptr::null_mut();
+            match (&*geometry).Open(&mut sink) {
+                S_OK => {},
+                e => {
+                    (&*geometry).Release();
+                    return Err(CanvasError::Other(e));
+                }
+            }
+
+            Ok(Path { geometry, sink })
+        }
+    }
+
+    /// Starts a new figure at `point`. `filled` selects whether the figure participates in fill
+    /// operations (`D2D1_FIGURE_BEGIN_FILLED`) or only in stroke operations (`D2D1_FIGURE_BEGIN_HOLLOW`).
+    pub fn begin_figure(&self, point: (f32, f32), filled: bool) {
+        let begin = match filled {
+            true => D2D1_FIGURE_BEGIN_FILLED,
+            false => D2D1_FIGURE_BEGIN_HOLLOW
+        };
+
+        unsafe {
+            (&*self.sink).BeginFigure(D2D1_POINT_2F { x: point.0, y: point.1 }, begin);
+        }
+    }
+
+    /// Adds a straight line segment from the figure's current point to `point`.
+    pub fn line_to(&self, point: (f32, f32)) {
+        unsafe {
+            (&*self.sink).AddLine(D2D1_POINT_2F { x: point.0, y: point.1 });
+        }
+    }
+
+    /// Adds a quadratic Bezier segment from the figure's current point to `end`, using `ctrl` as the control point.
+    pub fn quadratic_bezier_to(&self, ctrl: (f32, f32), end: (f32, f32)) {
+        unsafe {
+            (&*self.sink).AddQuadraticBezier(&D2D1_QUADRATIC_BEZIER_SEGMENT {
+                point1: D2D1_POINT_2F { x: ctrl.0, y: ctrl.1 },
+                point2: D2D1_POINT_2F { x: end.0, y: end.1 },
+            });
+        }
+    }
+
+    /// Adds a cubic Bezier segment from the figure's current point to `end`, using `c1` and `c2` as the control points.
+    pub fn cubic_bezier_to(&self, c1: (f32, f32), c2: (f32, f32), end: (f32, f32)) {
+        unsafe {
+            (&*self.sink).AddBezier(&D2D1_BEZIER_SEGMENT {
+                point1: D2D1_POINT_2F { x: c1.0, y: c1.1 },
+                point2: D2D1_POINT_2F { x: c2.0, y: c2.1 },
+                point3: D2D1_POINT_2F { x: end.0, y: end.1 },
+            });
+        }
+    }
+
+    /// Adds an elliptical arc segment from the figure's current point to `end`.
+    pub fn arc_to(&self, end: (f32, f32), radius: (f32, f32), rotation_angle: f32, sweep: SweepDirection, arc_size: ArcSize) {
+        let sweep_direction = match sweep {
+            SweepDirection::CounterClockwise => 0,
+            SweepDirection::Clockwise => 1,
+        };
+
+        let arc_size = match arc_size {
+            ArcSize::Small => 0,
+            ArcSize::Large => 1,
+        };
+
+        unsafe {
+            (&*self.sink).AddArc(&D2D1_ARC_SEGMENT {
+                point: D2D1_POINT_2F { x: end.0, y: end.1 },
+                size: D2D1_SIZE_F { width: radius.0, height: radius.1 },
+                rotationAngle: rotation_angle,
+                sweepDirection: sweep_direction,
+                arcSize: arc_size,
+            });
+        }
+    }
+
+    /// Ends the current figure. `closed` selects whether an implicit segment is added back to the figure's
+    /// start point (`D2D1_FIGURE_END_CLOSED`) or not (`D2D1_FIGURE_END_OPEN`), then closes the geometry sink.
+    /// Once closed, the path can be drawn with `CanvasDraw::draw_geometry` / `CanvasDraw::fill_geometry`.
+    pub fn close(&self, closed: bool) -> Result<(), CanvasError> {
+        let end = match closed {
+            true => D2D1_FIGURE_END_CLOSED,
+            false => D2D1_FIGURE_END_OPEN
+        };
+
+        unsafe {
+            let sink = &*self.sink;
+            sink.EndFigure(end);
+            match sink.Close() {
+                S_OK => Ok(()),
+                e => Err(CanvasError::Other(e))
+            }
+        }
+    }
+
+    fn as_geometry(&self) -> *mut ID2D1Geometry {
+        self.geometry as *mut ID2D1Geometry
+    }
+
+}
+
+impl Drop for Path {
+    fn drop(&mut self) {
+        unsafe {
+            (&*self.sink).Release();
+            (&*self.geometry).Release();
+        }
+    }
+}
+
+/**
+Describes how a layer pushed with `CanvasDraw::push_layer` is composited, mirroring `D2D1_LAYER_PARAMETERS`.
+
+Built with a default-then-set pattern: start from `LayerParameters::new()` and override only the fields that matter.
+*/
+pub struct LayerParameters<'a> {
+    content_bounds: Rect,
+    geometric_mask: Option<&'a Path>,
+    opacity: f32,
+    opacity_brush: Option<BaseBrush>,
+}
+
+impl<'a> LayerParameters<'a> {
+
+    /// Creates layer parameters covering the whole render target with no mask and full opacity.
+    pub fn new() -> LayerParameters<'a> {
+        LayerParameters {
+            content_bounds: Rect { left: std::f32::MIN, top: std::f32::MIN, right: std::f32::MAX, bottom: std::f32::MAX },
+            geometric_mask: None,
+            opacity: 1.0,
+            opacity_brush: None,
+        }
+    }
+
+    /// Restricts the layer's content to `rect`.
+    pub fn content_bounds(mut self, rect: Rect) -> LayerParameters<'a> {
+        self.content_bounds = rect;
+        self
+    }
+
+    /// Restricts the layer's content to the interior of `mask`.
+    pub fn geometric_mask(mut self, mask: &'a Path) -> LayerParameters<'a> {
+        self.geometric_mask = Some(mask);
+        self
+    }
+
+    /// Sets the constant opacity applied to the whole layer.
+    pub fn opacity(mut self, opacity: f32) -> LayerParameters<'a> {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Sets a brush whose alpha channel modulates the opacity of the layer.
+    /// Panics if the brush is not bound to the renderer
+    pub fn opacity_brush<B: TryInto<BaseBrush>>(mut self, brush: B) -> LayerParameters<'a> {
+        self.opacity_brush = match brush.try_into() {
+            Ok(b) => Some(b),
+            Err(_) => panic!("Brush is invalid")
+        };
+        self
+    }
+
+    fn as_d2d1(&self) -> D2D1_LAYER_PARAMETERS {
+        let identity = Matrix3x2F { matrix: [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]] };
+
+        D2D1_LAYER_PARAMETERS {
+            contentBounds: self.content_bounds,
+            geometricMask: self.geometric_mask.map(|p| p.as_geometry()).unwrap_or(ptr::null_mut()),
+            maskAntialiasMode: D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+            maskTransform: identity,
+            opacity: self.opacity,
+            opacityBrush: self.opacity_brush.as_ref().map(|b| b.0).unwrap_or(ptr::null_mut()),
+            layerOptions: D2D1_LAYER_OPTIONS_NONE,
+        }
+    }
+
+}
+
+/// A guard that pushes an axis-aligned clip rectangle on creation and pops it on `Drop`,
+/// so the `push_clip`/`pop_clip` pairing can't be leaked across an early return.
+pub struct ClipGuard<'a, 'b> {
+    draw: &'b CanvasDraw<'a>,
+}
+
+impl<'a, 'b> ClipGuard<'a, 'b> {
+    pub fn new(draw: &'b CanvasDraw<'a>, rect: &Rect) -> ClipGuard<'a, 'b> {
+        draw.push_clip(rect);
+        ClipGuard { draw }
+    }
+}
+
+impl<'a, 'b> Drop for ClipGuard<'a, 'b> {
+    fn drop(&mut self) {
+        self.draw.pop_clip();
+    }
+}
+
+/// A guard that pushes a layer on creation and pops it on `Drop`,
+/// so the `push_layer`/`pop_layer` pairing can't be leaked across an early return.
+pub struct LayerGuard<'a, 'b> {
+    draw: &'b CanvasDraw<'a>,
+}
+
+impl<'a, 'b> LayerGuard<'a, 'b> {
+    pub fn new(draw: &'b CanvasDraw<'a>, params: &LayerParameters) -> LayerGuard<'a, 'b> {
+        draw.push_layer(params);
+        LayerGuard { draw }
+    }
+}
+
+impl<'a, 'b> Drop for LayerGuard<'a, 'b> {
+    fn drop(&mut self) {
+        self.draw.pop_layer();
+    }
 }
@@ -1,6 +1,7 @@
+use winapi::shared::windef::RECT;
 use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, BS_AUTOCHECKBOX, BS_AUTO3STATE};
 use crate::win32::window_helper as wh;
-use crate::{Font, SystemError};
+use crate::{Bitmap, Icon, ImageList, Font, SystemError};
 use super::{ControlBase, ControlHandle};
 
 const NOT_BOUND: &'static str = "CheckBox is not yet bound to a winapi object";
@@ -25,6 +26,58 @@ pub enum CheckBoxState {
     Indeterminate
 }
 
+/// Placement of the image/icon set through `CheckBox::set_image_list`, relative to the label text.
+/// Mirrors the `uAlign` field of `BUTTON_IMAGELIST`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageAlign {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Center,
+}
+
+/// Placement of the check glyph relative to the label text. `Right` sets `BS_LEFTTEXT`; `Left` leaves it unset.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GlyphPosition {
+    Left,
+    Right,
+}
+
+/// Horizontal alignment of the checkbox's label text (`BS_LEFT`/`BS_CENTER`/`BS_RIGHT`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HTextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical alignment of the checkbox's label text (`BS_TOP`/`BS_VCENTER`/`BS_BOTTOM`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VTextAlign {
+    Top,
+    Center,
+    Bottom,
+}
+
+/// Layout of a checkbox's check glyph and label text, applied with `CheckBox::set_text_alignment`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextAlignment {
+    pub glyph: GlyphPosition,
+    pub horizontal: HTextAlign,
+    pub vertical: VTextAlign,
+}
+
+impl Default for TextAlignment {
+    fn default() -> TextAlignment {
+        TextAlignment {
+            glyph: GlyphPosition::Left,
+            horizontal: HTextAlign::Left,
+            vertical: VTextAlign::Center,
+        }
+    }
+}
+
 /**
 A check box consists of a square box and an application-defined label, icon, or bitmap that indicates a choice the user can make by selecting the button.
 Applications typically display check boxes to enable the user to choose one or more options that are not mutually exclusive.
@@ -47,6 +100,12 @@ impl CheckBox {
             check_state: CheckBoxState::Unchecked,
             flags: None,
             font: None,
+            image: None,
+            icon: None,
+            text_margin: None,
+            text_alignment: None,
+            multiline: false,
+            size_to_content: false,
             parent: None
         }
     }
@@ -231,6 +290,234 @@ impl CheckBox {
         BS_NOTIFY | WS_CHILD 
     }
 
+    /// Return `true` if the checkbox's label wraps within the control instead of being truncated
+    pub fn multiline(&self) -> bool {
+        use winapi::um::winuser::BS_MULTILINE;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        wh::get_style(handle) & BS_MULTILINE == BS_MULTILINE
+    }
+
+    /// Sets whether the checkbox's label wraps within the control instead of being truncated
+    pub fn set_multiline(&self, multiline: bool) {
+        use winapi::um::winuser::{BM_SETSTYLE, BS_MULTILINE};
+        use winapi::shared::minwindef::WPARAM;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let mut style = wh::get_style(handle) & !BS_MULTILINE;
+        if multiline {
+            style |= BS_MULTILINE;
+        }
+
+        wh::send_message(handle, BM_SETSTYLE, style as WPARAM, 1);
+    }
+
+    /// Sets the placement of the check glyph and the alignment of the label text
+    pub fn set_text_alignment(&self, align: TextAlignment) {
+        use winapi::um::winuser::{BM_SETSTYLE, BS_LEFTTEXT, BS_LEFT, BS_CENTER, BS_RIGHT, BS_TOP, BS_VCENTER, BS_BOTTOM};
+        use winapi::shared::minwindef::WPARAM;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let h_mask = BS_LEFT | BS_CENTER | BS_RIGHT;
+        let v_mask = BS_TOP | BS_VCENTER | BS_BOTTOM;
+
+        let h_bits = match align.horizontal {
+            HTextAlign::Left => BS_LEFT,
+            HTextAlign::Center => BS_CENTER,
+            HTextAlign::Right => BS_RIGHT,
+        };
+
+        let v_bits = match align.vertical {
+            VTextAlign::Top => BS_TOP,
+            VTextAlign::Center => BS_VCENTER,
+            VTextAlign::Bottom => BS_BOTTOM,
+        };
+
+        let mut style = wh::get_style(handle) & !(h_mask | v_mask | BS_LEFTTEXT);
+        style |= h_bits | v_bits;
+        if align.glyph == GlyphPosition::Right {
+            style |= BS_LEFTTEXT;
+        }
+
+        wh::send_message(handle, BM_SETSTYLE, style as WPARAM, 1);
+    }
+
+    /// Return the control's preferred size for its current label, image/icon and check glyph, so
+    /// long labels aren't clipped and the control looks right under high-DPI.
+    ///
+    /// Uses `BCM_GETIDEALSIZE` on comctl32 6+. On older comctl32, where that message isn't
+    /// supported, falls back to measuring the label with the control's current font and adding the
+    /// system checkbox glyph metrics, wrapping the label against the control's current width if
+    /// `BS_MULTILINE` is set. The fallback does not measure an image or icon.
+    pub fn ideal_size(&self) -> (u32, u32) {
+        use winapi::um::commctrl::BCM_GETIDEALSIZE;
+        use winapi::shared::windef::SIZE;
+        use winapi::shared::minwindef::LPARAM;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let mut size = SIZE { cx: 0, cy: 0 };
+        let supported = wh::send_message(handle, BCM_GETIDEALSIZE, 0, &mut size as *mut SIZE as LPARAM);
+
+        if supported != 0 && size.cx > 0 && size.cy > 0 {
+            return (size.cx as u32, size.cy as u32);
+        }
+
+        self.measure_ideal_size(handle)
+    }
+
+    /// Fallback for `ideal_size` on systems where `BCM_GETIDEALSIZE` isn't supported.
+    fn measure_ideal_size(&self, handle: winapi::shared::windef::HWND) -> (u32, u32) {
+        use winapi::um::winuser::{GetDC, ReleaseDC, GetSystemMetrics, SM_CXMENUCHECK, SM_CYMENUCHECK, DrawTextW, DT_CALCRECT, DT_WORDBREAK};
+        use winapi::um::wingdi::{SelectObject, GetTextExtentPoint32W};
+        use winapi::shared::windef::SIZE;
+
+        const SPACING: i32 = 4;
+
+        let text = self.text();
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        let font = wh::get_window_font(handle);
+
+        unsafe {
+            let dc = GetDC(handle);
+            let old_font = match font.is_null() {
+                true => std::ptr::null_mut(),
+                false => SelectObject(dc, font as _)
+            };
+
+            let glyph_w = GetSystemMetrics(SM_CXMENUCHECK);
+            let glyph_h = GetSystemMetrics(SM_CYMENUCHECK);
+
+            let (text_w, text_h) = match self.multiline() {
+                true => {
+                    let (ctrl_w, _) = self.size();
+                    let max_width = ((ctrl_w as i32) - glyph_w - SPACING).max(1);
+                    let mut rect = RECT { left: 0, top: 0, right: max_width, bottom: 0 };
+                    DrawTextW(dc, wide.as_ptr(), wide.len() as i32, &mut rect, DT_CALCRECT | DT_WORDBREAK);
+                    (rect.right - rect.left, rect.bottom - rect.top)
+                },
+                false => {
+                    let mut extent = SIZE { cx: 0, cy: 0 };
+                    GetTextExtentPoint32W(dc, wide.as_ptr(), wide.len() as i32, &mut extent);
+                    (extent.cx, extent.cy)
+                }
+            };
+
+            if !old_font.is_null() {
+                SelectObject(dc, old_font);
+            }
+            ReleaseDC(handle, dc);
+
+            let width = text_w + glyph_w + SPACING;
+            let height = text_h.max(glyph_h);
+
+            (width.max(0) as u32, height.max(0) as u32)
+        }
+    }
+
+    /// Sets the bitmap displayed next to the checkbox's label, or removes it if `image` is `None`.
+    pub fn set_image(&self, image: Option<&Bitmap>) {
+        use winapi::um::winuser::{BM_SETIMAGE, BM_SETSTYLE, IMAGE_BITMAP, BS_BITMAP, BS_ICON};
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
+        use std::ptr;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let style = wh::get_style(handle) & !(BS_ICON | BS_BITMAP);
+        let style = match image.is_some() {
+            true => style | BS_BITMAP,
+            false => style
+        };
+        wh::send_message(handle, BM_SETSTYLE, style as WPARAM, 1);
+
+        let bitmap_handle = image.map(|bmp| bmp.handle).unwrap_or(ptr::null_mut());
+        wh::send_message(handle, BM_SETIMAGE, IMAGE_BITMAP as WPARAM, bitmap_handle as LPARAM);
+    }
+
+    /// Sets the icon displayed next to the checkbox's label, or removes it if `icon` is `None`.
+    pub fn set_icon(&self, icon: Option<&Icon>) {
+        use winapi::um::winuser::{BM_SETIMAGE, BM_SETSTYLE, IMAGE_ICON, BS_ICON, BS_BITMAP};
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
+        use std::ptr;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let style = wh::get_style(handle) & !(BS_BITMAP | BS_ICON);
+        let style = match icon.is_some() {
+            true => style | BS_ICON,
+            false => style
+        };
+        wh::send_message(handle, BM_SETSTYLE, style as WPARAM, 1);
+
+        let icon_handle = icon.map(|ico| ico.handle).unwrap_or(ptr::null_mut());
+        wh::send_message(handle, BM_SETIMAGE, IMAGE_ICON as WPARAM, icon_handle as LPARAM);
+    }
+
+    /// Sets an image list used to render the checkbox's glyph/image, aligned relative to the label
+    /// as described by `align`. See the Win32 `BCM_SETIMAGELIST` message.
+    pub fn set_image_list(&self, image_list: &ImageList, align: ImageAlign) {
+        use winapi::um::commctrl::{
+            BCM_SETIMAGELIST, BUTTON_IMAGELIST,
+            BUTTON_IMAGELIST_ALIGN_LEFT, BUTTON_IMAGELIST_ALIGN_RIGHT,
+            BUTTON_IMAGELIST_ALIGN_TOP, BUTTON_IMAGELIST_ALIGN_BOTTOM, BUTTON_IMAGELIST_ALIGN_CENTER
+        };
+        use winapi::shared::minwindef::LPARAM;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let align = match align {
+            ImageAlign::Left => BUTTON_IMAGELIST_ALIGN_LEFT,
+            ImageAlign::Right => BUTTON_IMAGELIST_ALIGN_RIGHT,
+            ImageAlign::Top => BUTTON_IMAGELIST_ALIGN_TOP,
+            ImageAlign::Bottom => BUTTON_IMAGELIST_ALIGN_BOTTOM,
+            ImageAlign::Center => BUTTON_IMAGELIST_ALIGN_CENTER,
+        };
+
+        let mut info = BUTTON_IMAGELIST {
+            himl: image_list.handle,
+            margin: RECT { left: 0, top: 0, right: 0, bottom: 0 },
+            uAlign: align,
+        };
+
+        wh::send_message(handle, BCM_SETIMAGELIST, 0, &mut info as *mut BUTTON_IMAGELIST as LPARAM);
+    }
+
+    /// Return the margin between the checkbox's glyph/image and its label, as `(left, top, right, bottom)`
+    pub fn text_margin(&self) -> (i32, i32, i32, i32) {
+        use winapi::um::commctrl::BCM_GETTEXTMARGIN;
+        use winapi::shared::minwindef::LPARAM;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let mut margin = RECT { left: 0, top: 0, right: 0, bottom: 0 };
+        wh::send_message(handle, BCM_GETTEXTMARGIN, 0, &mut margin as *mut RECT as LPARAM);
+
+        (margin.left, margin.top, margin.right, margin.bottom)
+    }
+
+    /// Set the margin between the checkbox's glyph/image and its label, as `(left, top, right, bottom)`
+    pub fn set_text_margin(&self, margin: (i32, i32, i32, i32)) {
+        use winapi::um::commctrl::BCM_SETTEXTMARGIN;
+        use winapi::shared::minwindef::LPARAM;
+
+        if self.handle.blank() { panic!(NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+
+        let mut margin = RECT { left: margin.0, top: margin.1, right: margin.2, bottom: margin.3 };
+        wh::send_message(handle, BCM_SETTEXTMARGIN, 0, &mut margin as *mut RECT as LPARAM);
+    }
+
     /// Change the checkbox background color.
     fn hook_background_color(&self, c: [u8; 3]) {
         use crate::bind_raw_event_handler;
@@ -269,6 +556,12 @@ pub struct CheckBoxBuilder<'a> {
     check_state: CheckBoxState,
     flags: Option<CheckBoxFlags>,
     font: Option<&'a Font>,
+    image: Option<&'a Bitmap>,
+    icon: Option<&'a Icon>,
+    text_margin: Option<(i32, i32, i32, i32)>,
+    text_alignment: Option<TextAlignment>,
+    multiline: bool,
+    size_to_content: bool,
     parent: Option<ControlHandle>
 }
 
@@ -309,6 +602,37 @@ impl<'a> CheckBoxBuilder<'a> {
         self
     }
 
+    pub fn image(mut self, image: Option<&'a Bitmap>) -> CheckBoxBuilder<'a> {
+        self.image = image;
+        self
+    }
+
+    pub fn icon(mut self, icon: Option<&'a Icon>) -> CheckBoxBuilder<'a> {
+        self.icon = icon;
+        self
+    }
+
+    pub fn text_margin(mut self, margin: Option<(i32, i32, i32, i32)>) -> CheckBoxBuilder<'a> {
+        self.text_margin = margin;
+        self
+    }
+
+    pub fn text_alignment(mut self, align: Option<TextAlignment>) -> CheckBoxBuilder<'a> {
+        self.text_alignment = align;
+        self
+    }
+
+    pub fn multiline(mut self, multiline: bool) -> CheckBoxBuilder<'a> {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Resize the checkbox to `ideal_size()` once the control is created, so it fits its label and glyph automatically
+    pub fn size_to_content(mut self, size_to_content: bool) -> CheckBoxBuilder<'a> {
+        self.size_to_content = size_to_content;
+        self
+    }
+
     pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> CheckBoxBuilder<'a> {
         self.parent = Some(p.into());
         self
@@ -343,6 +667,31 @@ impl<'a> CheckBoxBuilder<'a> {
             out.hook_background_color(self.background_color.unwrap());
         }
 
+        if self.image.is_some() {
+            out.set_image(self.image);
+        }
+
+        if self.icon.is_some() {
+            out.set_icon(self.icon);
+        }
+
+        if let Some(margin) = self.text_margin {
+            out.set_text_margin(margin);
+        }
+
+        if let Some(align) = self.text_alignment {
+            out.set_text_alignment(align);
+        }
+
+        if self.multiline {
+            out.set_multiline(true);
+        }
+
+        if self.size_to_content {
+            let (w, h) = out.ideal_size();
+            out.set_size(w, h);
+        }
+
         out.set_check_state(self.check_state);
 
         Ok(())